@@ -1,9 +1,41 @@
 mod distance;
+pub mod vp;
 
-use std::{collections::VecDeque, iter::FromIterator, ops::Sub};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, VecDeque},
+    iter::FromIterator,
+    ops::Sub,
+};
 
 use serde::{Deserialize, Serialize};
 
+/// A distance metric over values of type `T`.
+///
+/// A BK-tree only relies on the triangle inequality, so any metric that
+/// satisfies it can drive the index: Levenshtein or Damerau-Levenshtein over
+/// strings, Hamming distance over byte vectors, or a custom domain metric over
+/// arbitrary `T`.
+pub trait Metric<T> {
+    /// Distance between `a` and `b`. Must be symmetric, zero iff `a == b`, and
+    /// obey the triangle inequality.
+    fn distance(&self, a: &T, b: &T) -> usize;
+}
+
+/// The Levenshtein (edit) distance metric over anything that is `AsRef<str>`.
+///
+/// This is the default metric for [`BkTree`], preserving the original
+/// string-oriented behaviour of the tree.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Levenshtein;
+
+impl<T: AsRef<str>> Metric<T> for Levenshtein {
+    #[inline]
+    fn distance(&self, a: &T, b: &T) -> usize {
+        distance::levenshtein_distance(a, b)
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
 struct Node<T> {
     word: T,
@@ -13,15 +45,17 @@ struct Node<T> {
 /// A BK-tree datastructure
 ///
 #[derive(Serialize, Deserialize)]
-pub struct BkTree<T: AsRef<str>> {
+pub struct BkTree<T, M = Levenshtein> {
     root: Option<Box<Node<T>>>,
+    #[serde(skip)]
+    metric: M,
 }
 
-impl<T: AsRef<str>> BkTree<T> {
-    /// Create a new BK-tree with a given distance function
+impl<T, M: Metric<T>> BkTree<T, M> {
+    /// Create a new BK-tree using the given distance metric
     #[inline]
-    pub fn new() -> Self {
-        Self { root: None }
+    pub fn new(metric: M) -> Self {
+        Self { root: None, metric }
     }
 
     /// Insert every element from a given iterator in the BK-tree
@@ -43,7 +77,7 @@ impl<T: AsRef<str>> BkTree<T> {
             Some(ref mut root_node) => {
                 let mut u = &mut **root_node;
                 loop {
-                    let k = distance::levenshtein_distance(&u.word, &val);
+                    let k = self.metric.distance(&u.word, &val);
                     if k == 0 {
                         return;
                     }
@@ -69,32 +103,181 @@ impl<T: AsRef<str>> BkTree<T> {
             }
         }
     }
+    /// Remove an element from the BK-tree, returning whether it was present
+    ///
+    /// Because a node's position depends on its parent, the matching node is
+    /// detached and every word in its subtree is collected and reinserted
+    /// through the normal [`insert`](Self::insert) path. Deleting the root
+    /// rebuilds the tree from the remaining words.
+    pub fn remove(&mut self, val: &T) -> bool {
+        let root_matches = match self.root {
+            Some(ref root) => self.metric.distance(&root.word, val) == 0,
+            None => return false,
+        };
+
+        let mut orphans = Vec::new();
+
+        if root_matches {
+            let root = self.root.take().unwrap();
+            drain_words(root.children, &mut orphans);
+            self.insert_all(orphans);
+            return true;
+        }
+
+        // Walk towards `val`: at each node the child at edge distance `k` is the
+        // only possible next step, and the target is that child when its own
+        // distance to `val` is zero.
+        let mut u = &mut **self.root.as_mut().unwrap();
+        loop {
+            let k = self.metric.distance(&u.word, val);
+            let pos = match u.children.iter().position(|(dist, _)| *dist == k) {
+                Some(pos) => pos,
+                None => return false,
+            };
+
+            if self.metric.distance(&u.children[pos].1.word, val) == 0 {
+                let (_, removed) = u.children.remove(pos);
+                drain_words(removed.children, &mut orphans);
+                self.insert_all(orphans);
+                return true;
+            }
+
+            u = &mut u.children[pos].1;
+        }
+    }
+
     /// Find the closest elements to a given value present in the BK-tree
     /// Returns pairs of element references and distances
+    #[inline]
     pub fn find(&self, val: &T, max_dist: usize) -> Vec<(&T, usize)> {
+        self.find_approx(val, max_dist, 1.0, None)
+    }
+
+    /// Approximate radius search that trades recall for speed.
+    ///
+    /// `ratio` (`>= 1.0`) tightens the triangle-inequality test used to decide
+    /// whether to descend into a child: a subtree is skipped unless its edge
+    /// distance `e` satisfies `|e - d| <= max_dist / ratio`, so a larger ratio
+    /// skips more subtrees and may miss some qualifying elements. When
+    /// `max_visits` is set the traversal stops after that many nodes have been
+    /// examined, returning whatever was found so far. With `ratio == 1.0` and
+    /// no visit cap this degrades to the exact [`find`](Self::find).
+    pub fn find_approx(
+        &self,
+        val: &T,
+        max_dist: usize,
+        ratio: f64,
+        max_visits: Option<usize>,
+    ) -> Vec<(&T, usize)> {
+        debug_assert!(ratio >= 1.0, "ratio must be >= 1.0");
         if self.root.is_none() {
             return vec![];
         }
 
+        let threshold = max_dist as f64 / ratio;
         let mut found = Vec::with_capacity(5);
+        let mut visits = 0;
 
         let mut candidates: VecDeque<&Node<T>> = VecDeque::with_capacity(511);
         candidates.push_back(self.root.as_ref().unwrap());
 
         while let Some(n) = candidates.pop_front() {
-            let distance = distance::levenshtein_distance(&n.word, &val);
+            if max_visits.is_some_and(|cap| visits >= cap) {
+                break;
+            }
+            visits += 1;
+
+            let distance = self.metric.distance(&n.word, val);
             if distance <= max_dist {
                 found.push((&n.word, distance));
             }
 
             candidates.extend(n.children.iter().filter_map(|(arc, node)| {
-                (abs_difference(*arc, distance) <= max_dist).then(|| node)
+                (abs_difference(*arc, distance) as f64 <= threshold).then(|| node)
             }));
         }
 
         found
     }
 
+    /// Find the `k` closest elements to a given value present in the BK-tree
+    ///
+    /// Returns pairs of element references and distances sorted by ascending
+    /// distance. The traversal keeps a bounded max-heap of the current `k`
+    /// best candidates: `tau` tracks the worst
+    /// distance in the heap (infinite until the heap is full), and a child is
+    /// only descended into when its stored edge distance `e` satisfies
+    /// `|e - d| <= tau` — the BK-tree triangle-inequality bound. As closer
+    /// items are found `tau` shrinks and prunes more aggressively.
+    #[inline]
+    pub fn find_nearest(&self, val: &T, k: usize) -> Vec<(&T, usize)> {
+        self.find_nearest_approx(val, k, 1.0, None)
+    }
+
+    /// Approximate k-nearest-neighbor search that trades recall for speed.
+    ///
+    /// Like [`find_nearest`](Self::find_nearest), but the descent test is
+    /// tightened by `ratio` (`>= 1.0`): a child is only explored when
+    /// `|e - d| <= tau / ratio`, so a larger ratio prunes more subtrees and may
+    /// return fewer than `k` or slightly farther elements. When `max_visits` is
+    /// set the traversal stops after that many nodes have been examined,
+    /// returning whatever was found so far. With `ratio == 1.0` and no visit
+    /// cap this degrades to the exact [`find_nearest`](Self::find_nearest).
+    pub fn find_nearest_approx(
+        &self,
+        val: &T,
+        k: usize,
+        ratio: f64,
+        max_visits: Option<usize>,
+    ) -> Vec<(&T, usize)> {
+        debug_assert!(ratio >= 1.0, "ratio must be >= 1.0");
+        if k == 0 || self.root.is_none() {
+            return vec![];
+        }
+
+        let mut heap: BinaryHeap<Candidate<T>> = BinaryHeap::with_capacity(k);
+        let mut tau = usize::MAX;
+        let mut visits = 0;
+
+        // Best-first traversal: a min-heap keyed by each subtree's `|e - d|`
+        // lower bound visits the most promising nodes first, so `tau` tightens
+        // early and prunes the remaining subtrees harder.
+        let mut queue: BinaryHeap<Reverse<Visit<T>>> = BinaryHeap::new();
+        queue.push(Reverse(Visit { bound: 0, node: self.root.as_ref().unwrap() }));
+
+        while let Some(Reverse(Visit { node: n, .. })) = queue.pop() {
+            if max_visits.is_some_and(|cap| visits >= cap) {
+                break;
+            }
+            visits += 1;
+
+            let d = self.metric.distance(&n.word, val);
+
+            if heap.len() < k {
+                heap.push(Candidate { dist: d, word: &n.word });
+                if heap.len() == k {
+                    tau = heap.peek().unwrap().dist;
+                }
+            } else if d < tau {
+                heap.pop();
+                heap.push(Candidate { dist: d, word: &n.word });
+                tau = heap.peek().unwrap().dist;
+            }
+
+            let threshold = tau as f64 / ratio;
+            for (e, node) in n.children.iter() {
+                let bound = abs_difference(*e, d);
+                if bound as f64 <= threshold {
+                    queue.push(Reverse(Visit { bound, node }));
+                }
+            }
+        }
+
+        let mut found: Vec<(&T, usize)> = heap.into_iter().map(|c| (c.word, c.dist)).collect();
+        found.sort_by_key(|&(_, d)| d);
+        found
+    }
+
     /// Convert the BK-tree into an iterator over its elements, in no particular order
     #[inline]
     pub fn into_iter(self) -> IntoIter<T> {
@@ -116,15 +299,85 @@ impl<T: AsRef<str>> BkTree<T> {
     }
 }
 
-impl<T: AsRef<str>> FromIterator<T> for BkTree<T> {
+impl<T, M: Metric<T> + Default> FromIterator<T> for BkTree<T, M> {
     #[inline]
     fn from_iter<A: IntoIterator<Item = T>>(iter: A) -> Self {
-        let mut bk = BkTree::new();
+        let mut bk = BkTree::new(M::default());
         bk.insert_all(iter);
         bk
     }
 }
 
+/// A k-NN heap entry, ordered purely by `dist` so a [`BinaryHeap`] keeps the
+/// current worst (largest-distance) candidate at its root for cheap eviction.
+struct Candidate<'a, T> {
+    dist: usize,
+    word: &'a T,
+}
+
+impl<T> PartialEq for Candidate<'_, T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.dist == other.dist
+    }
+}
+
+impl<T> Eq for Candidate<'_, T> {}
+
+impl<T> PartialOrd for Candidate<'_, T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Candidate<'_, T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.dist.cmp(&other.dist)
+    }
+}
+
+/// Collect every word stored in a set of child subtrees, consuming them.
+fn drain_words<T>(children: Vec<(usize, Node<T>)>, out: &mut Vec<T>) {
+    let mut stack: Vec<Node<T>> = children.into_iter().map(|(_, n)| n).collect();
+    while let Some(node) = stack.pop() {
+        out.push(node.word);
+        stack.extend(node.children.into_iter().map(|(_, n)| n));
+    }
+}
+
+/// A queued subtree for best-first k-NN traversal, ordered by its `|e - d|`
+/// lower bound so that — wrapped in [`Reverse`] — a [`BinaryHeap`] pops the
+/// most promising subtree first.
+struct Visit<'a, T> {
+    bound: usize,
+    node: &'a Node<T>,
+}
+
+impl<T> PartialEq for Visit<'_, T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.bound == other.bound
+    }
+}
+
+impl<T> Eq for Visit<'_, T> {}
+
+impl<T> PartialOrd for Visit<'_, T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Visit<'_, T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.bound.cmp(&other.bound)
+    }
+}
+
 #[inline]
 fn abs_difference<T: Sub<Output = T> + Ord>(x: T, y: T) -> T {
     if x < y {
@@ -134,7 +387,7 @@ fn abs_difference<T: Sub<Output = T> + Ord>(x: T, y: T) -> T {
     }
 }
 
-impl<T: AsRef<str>> IntoIterator for BkTree<T> {
+impl<T, M: Metric<T>> IntoIterator for BkTree<T, M> {
     type Item = T;
     type IntoIter = IntoIter<T>;
 
@@ -145,11 +398,11 @@ impl<T: AsRef<str>> IntoIterator for BkTree<T> {
 }
 
 /// Iterator over BK-tree elements
-pub struct IntoIter<T: AsRef<str>> {
+pub struct IntoIter<T> {
     queue: Vec<Node<T>>,
 }
 
-impl<T: AsRef<str>> Iterator for IntoIter<T> {
+impl<T> Iterator for IntoIter<T> {
     type Item = T;
 
     #[inline]
@@ -162,11 +415,11 @@ impl<T: AsRef<str>> Iterator for IntoIter<T> {
 }
 
 /// Iterator over BK-tree elements, by reference
-pub struct Iter<'a, T: AsRef<str>> {
+pub struct Iter<'a, T> {
     queue: Vec<&'a Node<T>>,
 }
 
-impl<'a, T: AsRef<str>> Iterator for Iter<'a, T> {
+impl<'a, T> Iterator for Iter<'a, T> {
     type Item = &'a T;
 
     #[inline]
@@ -180,15 +433,58 @@ impl<'a, T: AsRef<str>> Iterator for Iter<'a, T> {
 
 #[cfg(test)]
 mod tests {
-    use crate::BkTree;
+    use crate::{BkTree, Levenshtein};
     #[test]
     fn levenshtein_distance_test() {
-        let mut bk = BkTree::new();
+        let mut bk = BkTree::new(Levenshtein);
         bk.insert_all(vec![
             "book", "books", "boo", "boon", "cook", "cake", "cape", "cart",
         ]);
-        let (words, dists): (Vec<&str>, Vec<isize>) = bk.find("bo", 2).into_iter().unzip();
+        let (words, dists): (Vec<&str>, Vec<usize>) = bk.find(&"bo", 2).into_iter().unzip();
         assert_eq!(words, ["book", "boo", "boon"]);
         assert_eq!(dists, [2, 1, 2]);
     }
+
+    #[test]
+    fn find_nearest_test() {
+        let mut bk = BkTree::new(Levenshtein);
+        bk.insert_all(vec![
+            "book", "books", "boo", "boon", "cook", "cake", "cape", "cart",
+        ]);
+        let result = bk.find_nearest(&"bo", 3);
+        let dists: Vec<usize> = result.iter().map(|&(_, d)| d).collect();
+        assert_eq!(dists, [1, 2, 2]);
+        // The single closest element is unambiguous; the two distance-2
+        // matches may be returned in either order.
+        assert_eq!(result[0].0, &"boo");
+        let mut words: Vec<&str> = result.iter().map(|&(w, _)| *w).collect();
+        words.sort_unstable();
+        assert_eq!(words, ["boo", "book", "boon"]);
+    }
+
+    #[test]
+    fn remove_test() {
+        let mut bk = BkTree::new(Levenshtein);
+        bk.insert_all(vec![
+            "book", "books", "boo", "boon", "cook", "cake", "cape", "cart",
+        ]);
+
+        assert!(bk.remove(&"book"));
+        assert!(!bk.remove(&"book"));
+        assert!(bk.find(&"book", 0).is_empty());
+
+        // The descendants of the removed node survive the reinsertion.
+        let mut remaining: Vec<&str> = bk.iter().copied().collect();
+        remaining.sort_unstable();
+        assert_eq!(
+            remaining,
+            ["boo", "books", "boon", "cake", "cape", "cart", "cook"]
+        );
+
+        // A second removal still detaches the node and reinserts its subtree.
+        assert!(bk.remove(&"boo"));
+        let mut remaining: Vec<&str> = bk.iter().copied().collect();
+        remaining.sort_unstable();
+        assert_eq!(remaining, ["books", "boon", "cake", "cape", "cart", "cook"]);
+    }
 }
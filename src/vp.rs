@@ -0,0 +1,203 @@
+//! A vantage-point tree sharing the [`Metric`] abstraction with [`BkTree`].
+//!
+//! Where a [`BkTree`] keys its children by integer distance, a VP-tree splits
+//! the remaining points at the median distance to a chosen vantage point. This
+//! makes it a better fit for metrics whose distances are costly to recompute —
+//! edit distance over long strings, for example — while keeping the same
+//! pluggable metric and the same k-NN / approximate query API. Distances are
+//! the integer values returned by [`Metric::distance`]; real-valued distances
+//! are not supported.
+//!
+//! [`BkTree`]: crate::BkTree
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BinaryHeap;
+
+use crate::{abs_difference, Candidate, Levenshtein, Metric};
+
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+struct VpNode<T> {
+    vantage: T,
+    radius: usize,
+    inside: Option<Box<VpNode<T>>>,
+    outside: Option<Box<VpNode<T>>>,
+}
+
+/// A vantage-point tree over values of type `T`, driven by a [`Metric`]
+#[derive(Serialize, Deserialize)]
+pub struct VpTree<T, M = Levenshtein> {
+    root: Option<Box<VpNode<T>>>,
+    #[serde(skip)]
+    metric: M,
+}
+
+impl<T, M: Metric<T>> VpTree<T, M> {
+    /// Build a VP-tree in bulk from an iterator using the given metric
+    ///
+    /// The first item becomes the vantage point, the rest are sorted by their
+    /// distance to it and split at the median into an `inside` and an `outside`
+    /// subtree, with the median distance stored as the node's radius.
+    pub fn new<I: IntoIterator<Item = T>>(metric: M, items: I) -> Self {
+        let root = build(items.into_iter().collect(), &metric);
+        Self { root, metric }
+    }
+
+    /// Find the `k` closest elements to a given value present in the VP-tree
+    ///
+    /// Returns pairs of element references and distances sorted by ascending
+    /// distance.
+    #[inline]
+    pub fn find_nearest(&self, target: &T, k: usize) -> Vec<(&T, usize)> {
+        self.find_nearest_approx(target, k, 1.0, None)
+    }
+
+    /// Approximate k-nearest-neighbor search that trades recall for speed
+    ///
+    /// Mirrors [`BkTree::find_nearest_approx`]. At each node `d = metric(vantage,
+    /// target)` is compared against the node `radius`: the nearer child is
+    /// descended first and the farther child only when `|d - radius| <= tau /
+    /// ratio`, where `tau` is the current k-th-best distance. `ratio` (`>= 1.0`)
+    /// tightens that test and `max_visits`, if set, caps the number of visited
+    /// nodes, returning whatever was found so far. With `ratio == 1.0` and no
+    /// cap this is the exact [`find_nearest`](Self::find_nearest).
+    ///
+    /// [`BkTree::find_nearest_approx`]: crate::BkTree::find_nearest_approx
+    pub fn find_nearest_approx(
+        &self,
+        target: &T,
+        k: usize,
+        ratio: f64,
+        max_visits: Option<usize>,
+    ) -> Vec<(&T, usize)> {
+        debug_assert!(ratio >= 1.0, "ratio must be >= 1.0");
+        if k == 0 {
+            return vec![];
+        }
+
+        let mut heap: BinaryHeap<Candidate<T>> = BinaryHeap::with_capacity(k);
+        let mut tau = usize::MAX;
+        let mut visits = 0;
+
+        if let Some(ref root) = self.root {
+            self.search(root, target, k, ratio, max_visits, &mut heap, &mut tau, &mut visits);
+        }
+
+        let mut found: Vec<(&T, usize)> = heap.into_iter().map(|c| (c.word, c.dist)).collect();
+        found.sort_by_key(|&(_, d)| d);
+        found
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn search<'a>(
+        &self,
+        node: &'a VpNode<T>,
+        target: &T,
+        k: usize,
+        ratio: f64,
+        max_visits: Option<usize>,
+        heap: &mut BinaryHeap<Candidate<'a, T>>,
+        tau: &mut usize,
+        visits: &mut usize,
+    ) {
+        if max_visits.is_some_and(|cap| *visits >= cap) {
+            return;
+        }
+        *visits += 1;
+
+        let d = self.metric.distance(&node.vantage, target);
+
+        if heap.len() < k {
+            heap.push(Candidate { dist: d, word: &node.vantage });
+            if heap.len() == k {
+                *tau = heap.peek().unwrap().dist;
+            }
+        } else if d < *tau {
+            heap.pop();
+            heap.push(Candidate { dist: d, word: &node.vantage });
+            *tau = heap.peek().unwrap().dist;
+        }
+
+        // Descend the nearer child first so `tau` tightens before we decide
+        // whether the farther child can still hold a better match.
+        let (near, far) = if d < node.radius {
+            (&node.inside, &node.outside)
+        } else {
+            (&node.outside, &node.inside)
+        };
+
+        if let Some(child) = near {
+            self.search(child, target, k, ratio, max_visits, heap, tau, visits);
+        }
+
+        if abs_difference(d, node.radius) as f64 <= *tau as f64 / ratio {
+            if let Some(child) = far {
+                self.search(child, target, k, ratio, max_visits, heap, tau, visits);
+            }
+        }
+    }
+}
+
+impl<T, M: Metric<T> + Default> std::iter::FromIterator<T> for VpTree<T, M> {
+    #[inline]
+    fn from_iter<A: IntoIterator<Item = T>>(iter: A) -> Self {
+        VpTree::new(M::default(), iter)
+    }
+}
+
+/// Recursively build a VP-subtree: the first item is the vantage point, the
+/// rest are split at the median distance to it.
+fn build<T, M: Metric<T>>(mut items: Vec<T>, metric: &M) -> Option<Box<VpNode<T>>> {
+    if items.is_empty() {
+        return None;
+    }
+
+    let vantage = items.remove(0);
+    if items.is_empty() {
+        return Some(Box::new(VpNode {
+            vantage,
+            radius: 0,
+            inside: None,
+            outside: None,
+        }));
+    }
+
+    let mut ranked: Vec<(usize, T)> = items
+        .into_iter()
+        .map(|item| (metric.distance(&vantage, &item), item))
+        .collect();
+    ranked.sort_by_key(|(dist, _)| *dist);
+
+    let mid = ranked.len() / 2;
+    let radius = ranked[mid].0;
+    let outside: Vec<T> = ranked.split_off(mid).into_iter().map(|(_, item)| item).collect();
+    let inside: Vec<T> = ranked.into_iter().map(|(_, item)| item).collect();
+
+    Some(Box::new(VpNode {
+        vantage,
+        radius,
+        inside: build(inside, metric),
+        outside: build(outside, metric),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VpTree;
+    use crate::Levenshtein;
+
+    #[test]
+    fn find_nearest_test() {
+        let vp = VpTree::new(
+            Levenshtein,
+            vec!["book", "books", "boo", "boon", "cook", "cake", "cape", "cart"],
+        );
+        let result = vp.find_nearest(&"bo", 3);
+        let dists: Vec<usize> = result.iter().map(|&(_, d)| d).collect();
+        assert_eq!(dists, [1, 2, 2]);
+        assert_eq!(result[0].0, &"boo");
+        let mut words: Vec<&str> = result.iter().map(|&(w, _)| *w).collect();
+        words.sort_unstable();
+        assert_eq!(words, ["boo", "book", "boon"]);
+    }
+}